@@ -0,0 +1,49 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// A small TTL cache: entries expire after a fixed duration and are lazily
+/// evicted on lookup. Used to avoid re-fetching data that rarely changes
+/// (project field metadata, issue/PR node IDs) on every webhook event.
+pub(crate) struct TtlCache<K, V> {
+    ttl: Duration,
+    entries: Mutex<HashMap<K, Entry<V>>>,
+}
+
+struct Entry<V> {
+    value: V,
+    expires_at: Instant,
+}
+
+impl<K: Eq + Hash, V: Clone> TtlCache<K, V> {
+    pub(crate) fn new(ttl: Duration) -> Self {
+        TtlCache {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub(crate) fn get(&self, key: &K) -> Option<V> {
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get(key) {
+            Some(entry) if entry.expires_at > Instant::now() => Some(entry.value.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub(crate) fn insert(&self, key: K, value: V) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.insert(
+            key,
+            Entry {
+                value,
+                expires_at: Instant::now() + self.ttl,
+            },
+        );
+    }
+}
@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+/// Sync configuration: one entry per tracked repo, loaded from a TOML file at
+/// startup so a single deployment can fan events from many repos into
+/// different Project V2 boards.
+#[derive(Debug, Deserialize)]
+pub struct Config {
+    #[serde(rename = "repo")]
+    pub repos: Vec<RepoConfig>,
+    #[serde(default)]
+    pub notifier: NotifierConfig,
+}
+
+/// Where to send failure notifications when a sync fails. Disabled by
+/// default; set `enabled = true` and at least one sink to turn it on.
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct NotifierConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Generic outbound POST (Slack/Discord-style JSON body) for failures.
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+    #[serde(default)]
+    pub smtp: Option<SmtpConfig>,
+}
+
+/// SMTP settings used to email failure notifications.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SmtpConfig {
+    pub host: String,
+    #[serde(default = "default_smtp_port")]
+    pub port: u16,
+    pub username: String,
+    pub password: String,
+    pub from: String,
+    pub to: String,
+}
+
+fn default_smtp_port() -> u16 {
+    587
+}
+
+/// Per-repo settings: which project board events land on, which secret(s)
+/// validate its webhook deliveries, any overrides for the Status field
+/// name / option names used when the field isn't called "Status", and the
+/// rules mapping webhook events to a target column.
+#[derive(Debug, Deserialize)]
+pub struct RepoConfig {
+    pub full_name: String,
+    pub project_id: String,
+    #[serde(default)]
+    pub webhook_secrets: Vec<String>,
+    #[serde(default)]
+    pub status_field: Option<String>,
+    #[serde(default)]
+    pub status_options: HashMap<String, String>,
+    #[serde(default = "default_rules")]
+    pub rules: Vec<Rule>,
+}
+
+/// Maps a webhook action (and, for `labeled`/`unlabeled`, a specific label)
+/// to the column an item should be moved to. Rules are evaluated in order;
+/// the first match wins, and no match means the event is ignored.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Rule {
+    /// The webhook action this rule applies to, e.g. "opened", "closed",
+    /// "reopened", "labeled", "ready_for_review". PR `closed` events that
+    /// weren't merged are reported as the synthetic action
+    /// "closed_unmerged" so they can be matched (or left unmatched)
+    /// separately from a real merge.
+    pub on: String,
+    /// Only matches when the label added/removed has this name. Ignored
+    /// for actions other than `labeled`/`unlabeled`.
+    #[serde(default)]
+    pub label: Option<String>,
+    pub set_status: String,
+}
+
+/// The built-in rules, used when a repo doesn't configure its own: mirrors
+/// the service's original hardcoded behavior, and also moves reopened items
+/// back to "Todo".
+fn default_rules() -> Vec<Rule> {
+    vec![
+        Rule {
+            on: "opened".to_string(),
+            label: None,
+            set_status: "Todo".to_string(),
+        },
+        Rule {
+            on: "reopened".to_string(),
+            label: None,
+            set_status: "Todo".to_string(),
+        },
+        Rule {
+            on: "closed".to_string(),
+            label: None,
+            set_status: "Done".to_string(),
+        },
+    ]
+}
+
+impl RepoConfig {
+    /// Name of the single-select field that tracks workflow status, falling
+    /// back to "Status" when the repo doesn't override it.
+    pub fn status_field(&self) -> &str {
+        self.status_field.as_deref().unwrap_or("Status")
+    }
+
+    /// Maps a logical status (e.g. "Todo") to the option name configured for
+    /// this repo's board, falling back to the logical name unchanged.
+    pub fn status_option<'a>(&'a self, status: &'a str) -> &'a str {
+        self.status_options
+            .get(status)
+            .map(|s| s.as_str())
+            .unwrap_or(status)
+    }
+
+    /// Finds the first configured rule matching `action` (and `label`, for
+    /// labeled/unlabeled actions), returning the column it maps to.
+    pub fn resolve_status(&self, action: &str, label: Option<&str>) -> Option<&str> {
+        self.rules
+            .iter()
+            .find(|rule| {
+                rule.on == action
+                    && match &rule.label {
+                        Some(want) if matches!(action, "labeled" | "unlabeled") => {
+                            Some(want.as_str()) == label
+                        }
+                        _ => true,
+                    }
+            })
+            .map(|rule| rule.set_status.as_str())
+    }
+}
+
+impl Config {
+    /// Loads and parses the config file at `path` (TOML).
+    pub fn load(path: &str) -> anyhow::Result<Config> {
+        let raw = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("failed to read config {path}: {e}"))?;
+        let config: Config = toml::from_str(&raw)
+            .map_err(|e| anyhow::anyhow!("failed to parse config {path}: {e}"))?;
+        Ok(config)
+    }
+
+    /// Finds the repo entry matching a webhook's `repository.full_name`.
+    pub fn repo(&self, full_name: &str) -> Option<&RepoConfig> {
+        self.repos.iter().find(|r| r.full_name == full_name)
+    }
+}
@@ -0,0 +1,418 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use reqwest::Client;
+
+use crate::cache::TtlCache;
+
+const FIELD_CACHE_TTL: Duration = Duration::from_secs(300);
+const NODE_ID_CACHE_TTL: Duration = Duration::from_secs(3600);
+
+/// An issue or pull request as returned by GitHub's REST API, trimmed to
+/// the fields reconciliation needs to compute the expected Status.
+#[derive(Debug, Clone)]
+pub(crate) struct RemoteItem {
+    pub(crate) html_url: String,
+    pub(crate) number: u64,
+    pub(crate) state: String,
+    pub(crate) pull_request: bool,
+    pub(crate) merged: Option<bool>,
+}
+
+impl RemoteItem {
+    /// The column this item belongs in, or `None` for states reconciliation
+    /// should leave untouched. Mirrors the webhook path's default rules
+    /// (custom per-repo rules are not consulted): a closed issue is
+    /// always "Done"; a closed PR is only "Done" once merged, and a closed
+    /// but unmerged PR (`closed_unmerged` for the webhook) isn't synced to
+    /// any column rather than being mistaken for "Done".
+    pub(crate) fn expected_status(&self) -> Option<&'static str> {
+        if self.pull_request {
+            if self.merged == Some(true) {
+                Some("Done")
+            } else if self.state == "closed" {
+                None
+            } else {
+                Some("Todo")
+            }
+        } else {
+            Some(if self.state == "closed" { "Done" } else { "Todo" })
+        }
+    }
+}
+
+/// A single-select field's ID and its option name -> option ID map.
+#[derive(Debug, Clone)]
+struct FieldInfo {
+    field_id: String,
+    options: HashMap<String, String>,
+}
+
+/// GitHub access needed to sync issues/PRs onto a Project V2 board,
+/// extracted as a trait so webhook and reconciliation logic can be tested
+/// against a mock instead of the real network.
+#[async_trait::async_trait]
+pub(crate) trait GitHubClient: Send + Sync {
+    /// Resolves an issue/PR `html_url` to its GraphQL node ID.
+    async fn get_node_id(&self, html_url: &str) -> anyhow::Result<String>;
+
+    /// Adds an issue/PR to a project board, returning the resulting item ID.
+    async fn add_to_project(&self, project_id: &str, content_url: &str) -> anyhow::Result<String>;
+
+    /// Sets a single-select field (e.g. "Status") on a project item.
+    async fn update_status(
+        &self,
+        project_id: &str,
+        item_id: &str,
+        field_name: &str,
+        option_name: &str,
+    ) -> anyhow::Result<()>;
+
+    /// Lists every issue and PR in a repo (REST `state=all`, paginated).
+    async fn list_issues(&self, repo_full_name: &str) -> anyhow::Result<Vec<RemoteItem>>;
+
+    /// Maps each project item's content URL to its current value for
+    /// `field_name`, paginating over the board's items.
+    async fn project_item_statuses(
+        &self,
+        project_id: &str,
+        field_name: &str,
+    ) -> anyhow::Result<HashMap<String, String>>;
+}
+
+/// Real implementation backed by `reqwest` against api.github.com, with a
+/// TTL cache over field metadata and node IDs to cut down on repeat calls.
+pub(crate) struct RestGitHubClient {
+    http: Client,
+    github_token: String,
+    field_cache: TtlCache<String, FieldInfo>,
+    node_id_cache: TtlCache<String, String>,
+}
+
+impl RestGitHubClient {
+    pub(crate) fn new(http: Client, github_token: String) -> Self {
+        RestGitHubClient {
+            http,
+            github_token,
+            field_cache: TtlCache::new(FIELD_CACHE_TTL),
+            node_id_cache: TtlCache::new(NODE_ID_CACHE_TTL),
+        }
+    }
+
+    async fn fetch_field_info(&self, project_id: &str, field_name: &str) -> anyhow::Result<FieldInfo> {
+        let field_query = r#"query($projectId: ID!) {
+            node(id: $projectId) {
+                ... on ProjectV2 {
+                    fields(first: 20) {
+                        nodes {
+                            ... on ProjectV2SingleSelectField {
+                                id
+                                name
+                                options { id name }
+                            }
+                        }
+                    }
+                }
+            }
+        }"#;
+
+        let body = serde_json::json!({
+            "query": field_query,
+            "variables": { "projectId": project_id }
+        });
+
+        let json: serde_json::Value = self
+            .http
+            .post("https://api.github.com/graphql")
+            .header("Authorization", format!("Bearer {}", self.github_token))
+            .header("User-Agent", "github-project-sync")
+            .json(&body)
+            .send()
+            .await?
+            .json()
+            .await?;
+
+        let fields = json["data"]["node"]["fields"]["nodes"]
+            .as_array()
+            .ok_or_else(|| anyhow::anyhow!("no fields found: {json}"))?;
+
+        let field = fields
+            .iter()
+            .find(|field| field["name"].as_str() == Some(field_name))
+            .ok_or_else(|| anyhow::anyhow!("{field_name} field not found"))?;
+
+        let field_id = field["id"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("{field_name} field has no id: {field}"))?
+            .to_string();
+
+        let options = field["options"]
+            .as_array()
+            .map(|options| {
+                options
+                    .iter()
+                    .filter_map(|opt| {
+                        let name = opt["name"].as_str()?;
+                        let id = opt["id"].as_str()?;
+                        Some((name.to_string(), id.to_string()))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(FieldInfo { field_id, options })
+    }
+}
+
+#[async_trait::async_trait]
+impl GitHubClient for RestGitHubClient {
+    async fn get_node_id(&self, html_url: &str) -> anyhow::Result<String> {
+        if let Some(node_id) = self.node_id_cache.get(&html_url.to_string()) {
+            return Ok(node_id);
+        }
+
+        let api_url = html_url
+            .replace("https://github.com/", "https://api.github.com/repos/")
+            .replace("/pull/", "/pulls/");
+
+        let json: serde_json::Value = self
+            .http
+            .get(&api_url)
+            .header("Authorization", format!("Bearer {}", self.github_token))
+            .header("User-Agent", "github-project-sync")
+            .header("Accept", "application/vnd.github+json")
+            .send()
+            .await?
+            .json()
+            .await?;
+
+        let node_id = json["node_id"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("no node_id in response: {json}"))?
+            .to_string();
+
+        self.node_id_cache.insert(html_url.to_string(), node_id.clone());
+        Ok(node_id)
+    }
+
+    async fn add_to_project(&self, project_id: &str, content_url: &str) -> anyhow::Result<String> {
+        let query = r#"mutation($projectId: ID!, $contentId: ID!) {
+            addProjectV2ItemById(input: {projectId: $projectId, contentId: $contentId}) {
+                item { id }
+            }
+        }"#;
+
+        let node_id = self.get_node_id(content_url).await?;
+
+        let body = serde_json::json!({
+            "query": query,
+            "variables": { "projectId": project_id, "contentId": node_id }
+        });
+
+        let json: serde_json::Value = self
+            .http
+            .post("https://api.github.com/graphql")
+            .header("Authorization", format!("Bearer {}", self.github_token))
+            .header("User-Agent", "github-project-sync")
+            .json(&body)
+            .send()
+            .await?
+            .json()
+            .await?;
+
+        if let Some(errors) = json.get("errors") {
+            anyhow::bail!("GraphQL errors: {errors}");
+        }
+
+        json["data"]["addProjectV2ItemById"]["item"]["id"]
+            .as_str()
+            .map(|s| s.to_string())
+            .ok_or_else(|| anyhow::anyhow!("no item ID in response: {json}"))
+    }
+
+    async fn update_status(
+        &self,
+        project_id: &str,
+        item_id: &str,
+        field_name: &str,
+        option_name: &str,
+    ) -> anyhow::Result<()> {
+        let cache_key = format!("{project_id}:{field_name}");
+        let field_info = match self.field_cache.get(&cache_key) {
+            Some(info) => info,
+            None => {
+                let info = self.fetch_field_info(project_id, field_name).await?;
+                self.field_cache.insert(cache_key, info.clone());
+                info
+            }
+        };
+
+        let option_id = field_info
+            .options
+            .get(option_name)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("{field_name} option '{option_name}' not found"))?;
+
+        let mutation = r#"mutation($projectId: ID!, $itemId: ID!, $fieldId: ID!, $optionId: String!) {
+            updateProjectV2ItemFieldValue(input: {
+                projectId: $projectId
+                itemId: $itemId
+                fieldId: $fieldId
+                value: { singleSelectOptionId: $optionId }
+            }) {
+                projectV2Item { id }
+            }
+        }"#;
+
+        let body = serde_json::json!({
+            "query": mutation,
+            "variables": {
+                "projectId": project_id,
+                "itemId": item_id,
+                "fieldId": field_info.field_id,
+                "optionId": option_id
+            }
+        });
+
+        let json: serde_json::Value = self
+            .http
+            .post("https://api.github.com/graphql")
+            .header("Authorization", format!("Bearer {}", self.github_token))
+            .header("User-Agent", "github-project-sync")
+            .json(&body)
+            .send()
+            .await?
+            .json()
+            .await?;
+
+        if let Some(errors) = json.get("errors") {
+            anyhow::bail!("status update errors: {errors}");
+        }
+
+        Ok(())
+    }
+
+    async fn list_issues(&self, repo_full_name: &str) -> anyhow::Result<Vec<RemoteItem>> {
+        const PER_PAGE: u32 = 100;
+        let mut items = Vec::new();
+        let mut page = 1;
+
+        loop {
+            let url = format!(
+                "https://api.github.com/repos/{repo_full_name}/issues?state=all&per_page={PER_PAGE}&page={page}"
+            );
+
+            let batch: Vec<serde_json::Value> = self
+                .http
+                .get(&url)
+                .header("Authorization", format!("Bearer {}", self.github_token))
+                .header("User-Agent", "github-project-sync")
+                .header("Accept", "application/vnd.github+json")
+                .send()
+                .await?
+                .json()
+                .await?;
+
+            let got = batch.len();
+            for issue in &batch {
+                let Some(html_url) = issue["html_url"].as_str() else {
+                    continue;
+                };
+                // The issues-list endpoint never returns a top-level `merged`
+                // flag for PRs, only `pull_request.merged_at`.
+                let pull_request = issue.get("pull_request");
+                let merged = pull_request.map(|pr| !pr["merged_at"].is_null());
+                items.push(RemoteItem {
+                    html_url: html_url.to_string(),
+                    number: issue["number"].as_u64().unwrap_or(0),
+                    state: issue["state"].as_str().unwrap_or("open").to_string(),
+                    pull_request: pull_request.is_some(),
+                    merged,
+                });
+            }
+
+            if (got as u32) < PER_PAGE {
+                break;
+            }
+            page += 1;
+        }
+
+        Ok(items)
+    }
+
+    async fn project_item_statuses(
+        &self,
+        project_id: &str,
+        field_name: &str,
+    ) -> anyhow::Result<HashMap<String, String>> {
+        let query = r#"query($projectId: ID!, $after: String, $fieldName: String!) {
+            node(id: $projectId) {
+                ... on ProjectV2 {
+                    items(first: 100, after: $after) {
+                        pageInfo { hasNextPage endCursor }
+                        nodes {
+                            content {
+                                ... on Issue { url }
+                                ... on PullRequest { url }
+                            }
+                            fieldValueByName(name: $fieldName) {
+                                ... on ProjectV2ItemFieldSingleSelectValue { name }
+                            }
+                        }
+                    }
+                }
+            }
+        }"#;
+
+        let mut statuses = HashMap::new();
+        let mut after: Option<String> = None;
+
+        loop {
+            let body = serde_json::json!({
+                "query": query,
+                "variables": { "projectId": project_id, "after": after, "fieldName": field_name }
+            });
+
+            let json: serde_json::Value = self
+                .http
+                .post("https://api.github.com/graphql")
+                .header("Authorization", format!("Bearer {}", self.github_token))
+                .header("User-Agent", "github-project-sync")
+                .json(&body)
+                .send()
+                .await?
+                .json()
+                .await?;
+
+            if let Some(errors) = json.get("errors") {
+                anyhow::bail!("GraphQL errors while reading project items: {errors}");
+            }
+
+            let items = json["data"]["node"]["items"]["nodes"]
+                .as_array()
+                .cloned()
+                .unwrap_or_default();
+            let got = items.len();
+
+            for item in &items {
+                let Some(url) = item["content"]["url"].as_str() else {
+                    continue;
+                };
+                if let Some(name) = item["fieldValueByName"]["name"].as_str() {
+                    statuses.insert(url.to_string(), name.to_string());
+                }
+            }
+
+            let page_info = &json["data"]["node"]["items"]["pageInfo"];
+            if page_info["hasNextPage"].as_bool() != Some(true) || got < 100 {
+                break;
+            }
+            after = page_info["endCursor"].as_str().map(str::to_string);
+            if after.is_none() {
+                break;
+            }
+        }
+
+        Ok(statuses)
+    }
+}
@@ -1,3 +1,10 @@
+mod cache;
+mod config;
+mod github;
+mod notifier;
+mod queue;
+mod reconcile;
+
 use axum::{
     body::Bytes,
     extract::State,
@@ -10,16 +17,24 @@ use hmac::{Hmac, Mac};
 use reqwest::Client;
 use sha2::Sha256;
 use std::sync::Arc;
+use std::time::Duration;
 use tracing::{error, info, warn};
 
-const ALLOWED_REPOS: &[&str] = &["pikarama", "brick-directory"];
+use config::{Config, RepoConfig};
+use github::{GitHubClient, RestGitHubClient};
+use queue::Queue;
+
+/// How many pending events the worker pulls per poll.
+const WORKER_BATCH_SIZE: i64 = 20;
+/// How long the worker sleeps between polls when there's nothing due.
+const WORKER_POLL_INTERVAL: Duration = Duration::from_secs(2);
 
 #[derive(Clone)]
-struct AppState {
-    webhook_secret: String,
-    github_token: String,
-    project_id: String,
-    http: Client,
+pub(crate) struct AppState {
+    pub(crate) config: Arc<Config>,
+    pub(crate) http: Client,
+    pub(crate) github: Arc<dyn GitHubClient>,
+    queue: Queue,
 }
 
 #[derive(serde::Deserialize, Debug)]
@@ -28,6 +43,8 @@ struct WebhookPayload {
     issue: Option<Issue>,
     pull_request: Option<PullRequest>,
     repository: Option<Repository>,
+    /// Present on `labeled`/`unlabeled` events: the label that was added or removed.
+    label: Option<Label>,
 }
 
 #[derive(serde::Deserialize, Debug)]
@@ -35,6 +52,10 @@ struct Issue {
     html_url: String,
     number: u64,
     title: String,
+    #[serde(default)]
+    labels: Vec<Label>,
+    #[serde(default)]
+    assignees: Vec<Assignee>,
 }
 
 #[derive(serde::Deserialize, Debug)]
@@ -43,11 +64,26 @@ struct PullRequest {
     number: u64,
     title: String,
     merged: Option<bool>,
+    #[serde(default)]
+    labels: Vec<Label>,
+    #[serde(default)]
+    assignees: Vec<Assignee>,
+    #[serde(default)]
+    draft: bool,
 }
 
 #[derive(serde::Deserialize, Debug)]
-struct Repository {
+struct Label {
     name: String,
+}
+
+#[derive(serde::Deserialize, Debug)]
+struct Assignee {
+    login: String,
+}
+
+#[derive(serde::Deserialize, Debug)]
+struct Repository {
     full_name: String,
 }
 
@@ -60,14 +96,28 @@ async fn main() {
         )
         .init();
 
+    let config_path =
+        std::env::var("CONFIG_PATH").unwrap_or_else(|_| "config.toml".to_string());
+    let config = Config::load(&config_path).expect("failed to load config");
+
+    let database_url =
+        std::env::var("DATABASE_URL").unwrap_or_else(|_| "sqlite://events.db".to_string());
+    let queue = Queue::connect(&database_url)
+        .await
+        .expect("failed to open event queue");
+
+    let http = Client::new();
+    let github_token = std::env::var("GITHUB_TOKEN").expect("GITHUB_TOKEN required");
+
     let state = Arc::new(AppState {
-        webhook_secret: std::env::var("WEBHOOK_SECRET").expect("WEBHOOK_SECRET required"),
-        github_token: std::env::var("GITHUB_TOKEN").expect("GITHUB_TOKEN required"),
-        project_id: std::env::var("PROJECT_ID")
-            .unwrap_or_else(|_| "PVT_kwHOAAoTtc4BO2oX".to_string()),
-        http: Client::new(),
+        config: Arc::new(config),
+        http: http.clone(),
+        github: Arc::new(RestGitHubClient::new(http, github_token)),
+        queue,
     });
 
+    let worker_handle = tokio::spawn(run_worker(state.clone()));
+
     let port: u16 = std::env::var("PORT")
         .unwrap_or_else(|_| "3000".to_string())
         .parse()
@@ -76,24 +126,140 @@ async fn main() {
     let app = Router::new()
         .route("/health", get(health))
         .route("/webhook/github", post(webhook))
-        .with_state(state);
+        .with_state(state.clone());
 
     let addr = format!("0.0.0.0:{port}");
     info!("Listening on {addr}");
     let listener = tokio::net::TcpListener::bind(&addr).await.unwrap();
-    axum::serve(listener, app).await.unwrap();
+
+    let api_service = async { axum::serve(listener, app).await.map_err(anyhow::Error::from) };
+    let reconcile_loop = reconcile::run(state);
+    // Surfaces a worker panic as a service failure instead of letting the
+    // queue silently back up while the HTTP server keeps answering 200s.
+    let worker_supervisor = async { worker_handle.await.map_err(anyhow::Error::from) };
+
+    if let Err(e) = tokio::try_join!(api_service, reconcile_loop, worker_supervisor) {
+        error!("Service task exited with error: {e}");
+    }
+}
+
+/// Drains `Pending` events from the queue, dispatching each through the same
+/// handlers the webhook would have called inline. Keeps polling forever;
+/// failures are recorded back onto the queue for exponential-backoff retry.
+async fn run_worker(state: Arc<AppState>) {
+    loop {
+        match state.queue.claim_due(WORKER_BATCH_SIZE).await {
+            Ok(events) if events.is_empty() => {
+                tokio::time::sleep(WORKER_POLL_INTERVAL).await;
+            }
+            Ok(events) => {
+                for event in events {
+                    process_queued_event(&state, event).await;
+                }
+            }
+            Err(e) => {
+                error!("Failed to poll event queue: {e}");
+                tokio::time::sleep(WORKER_POLL_INTERVAL).await;
+            }
+        }
+    }
+}
+
+async fn process_queued_event(state: &AppState, event: queue::QueuedEvent) {
+    let result = dispatch_event(state, &event).await;
+
+    match result {
+        Ok(()) => {
+            if let Err(e) = state.queue.mark_done(event.id).await {
+                error!(event_id = event.id, "Failed to mark event done: {e}");
+            }
+        }
+        Err(status) => {
+            let attempts = event.attempts + 1;
+            warn!(
+                event_id = event.id,
+                attempts, ?status, "Event processing failed, scheduling retry"
+            );
+            if let Err(e) = state.queue.mark_failed(event.id, attempts).await {
+                error!(event_id = event.id, "Failed to record event failure: {e}");
+            }
+        }
+    }
+}
+
+async fn dispatch_event(state: &AppState, event: &queue::QueuedEvent) -> Result<(), StatusCode> {
+    let Some(repo_cfg) = state.config.repo(&event.repo_full_name) else {
+        warn!(repo = event.repo_full_name, "No config for queued event's repo, dropping");
+        return Ok(());
+    };
+
+    let payload: WebhookPayload = serde_json::from_slice(&event.payload).map_err(|e| {
+        error!("Failed to parse queued payload: {e}");
+        StatusCode::BAD_REQUEST
+    })?;
+
+    match event.event_type.as_str() {
+        "issues" => handle_issue(state, repo_cfg, &payload).await.map(|_| ()),
+        "pull_request" => handle_pr(state, repo_cfg, &payload).await.map(|_| ()),
+        _ => Ok(()),
+    }
 }
 
 async fn health() -> impl IntoResponse {
     Json(serde_json::json!({"status": "ok"}))
 }
 
+/// Fires a failure notification to the configured sinks in the background,
+/// so a down SMTP relay or webhook endpoint never blocks the sync itself.
+fn notify_failure(state: &AppState, repo: &str, event: &str, item_number: Option<u64>, detail: String) {
+    let http = state.http.clone();
+    let config = state.config.notifier.clone();
+    let repo = repo.to_string();
+    let event = event.to_string();
+    tokio::spawn(async move {
+        notifier::notify(
+            &http,
+            &config,
+            notifier::Failure {
+                repo,
+                event,
+                item_number,
+                detail,
+            },
+        )
+        .await;
+    });
+}
+
 async fn webhook(
     State(state): State<Arc<AppState>>,
     headers: HeaderMap,
     body: Bytes,
 ) -> Result<impl IntoResponse, StatusCode> {
-    // Validate signature
+    let payload: WebhookPayload = serde_json::from_slice(&body).map_err(|e| {
+        error!("Failed to parse payload: {e}");
+        StatusCode::BAD_REQUEST
+    })?;
+
+    // Filter by repo
+    let full_name = payload
+        .repository
+        .as_ref()
+        .map(|r| r.full_name.as_str())
+        .unwrap_or("");
+
+    let Some(repo_cfg) = state.config.repo(full_name) else {
+        info!("Ignoring event from repo: {full_name}");
+        return Ok(Json(serde_json::json!({"status": "ignored", "reason": "repo not tracked"})));
+    };
+
+    let item_number = payload
+        .issue
+        .as_ref()
+        .map(|i| i.number)
+        .or_else(|| payload.pull_request.as_ref().map(|pr| pr.number));
+
+    // Validate signature against any secret configured for this repo
     let sig = headers
         .get("x-hub-signature-256")
         .and_then(|v| v.to_str().ok())
@@ -101,13 +267,18 @@ async fn webhook(
 
     let sig = sig.strip_prefix("sha256=").ok_or(StatusCode::UNAUTHORIZED)?;
 
-    let mut mac = Hmac::<Sha256>::new_from_slice(state.webhook_secret.as_bytes())
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-    mac.update(&body);
-    let expected = hex::encode(mac.finalize().into_bytes());
+    let valid = repo_cfg.webhook_secrets.iter().any(|secret| {
+        let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(secret.as_bytes()) else {
+            return false;
+        };
+        mac.update(&body);
+        let expected = hex::encode(mac.finalize().into_bytes());
+        constant_time_eq(sig.as_bytes(), expected.as_bytes())
+    });
 
-    if !constant_time_eq(sig.as_bytes(), expected.as_bytes()) {
-        warn!("Invalid webhook signature");
+    if !valid {
+        warn!(repo = full_name, "Invalid webhook signature");
+        notify_failure(&state, full_name, "webhook", item_number, "invalid webhook signature".to_string());
         return Err(StatusCode::UNAUTHORIZED);
     }
 
@@ -116,303 +287,503 @@ async fn webhook(
         .and_then(|v| v.to_str().ok())
         .unwrap_or("unknown");
 
-    let payload: WebhookPayload =
-        serde_json::from_slice(&body).map_err(|e| {
-            error!("Failed to parse payload: {e}");
-            StatusCode::BAD_REQUEST
-        })?;
+    if event != "issues" && event != "pull_request" {
+        info!("Ignoring event type: {event}");
+        return Ok(Json(serde_json::json!({"status": "ignored"})));
+    }
 
-    // Filter by repo
-    let repo_name = payload
-        .repository
-        .as_ref()
-        .map(|r| r.name.as_str())
-        .unwrap_or("");
+    let delivery_id = headers
+        .get("x-github-delivery")
+        .and_then(|v| v.to_str().ok())
+        .ok_or(StatusCode::BAD_REQUEST)?;
 
-    if !ALLOWED_REPOS.contains(&repo_name) {
-        info!("Ignoring event from repo: {repo_name}");
-        return Ok(Json(serde_json::json!({"status": "ignored", "reason": "repo not tracked"})));
-    }
+    info!(event, action = %payload.action, repo = full_name, delivery_id, "Queuing webhook");
 
-    info!(event, action = %payload.action, repo = repo_name, "Processing webhook");
+    let queued = state
+        .queue
+        .enqueue(delivery_id, event, full_name, &body)
+        .await
+        .map_err(|e| {
+            error!("Failed to enqueue event: {e}");
+            notify_failure(&state, full_name, event, item_number, format!("failed to enqueue event: {e}"));
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
 
-    match event {
-        "issues" => handle_issue(&state, &payload).await,
-        "pull_request" => handle_pr(&state, &payload).await,
-        _ => {
-            info!("Ignoring event type: {event}");
-            Ok(Json(serde_json::json!({"status": "ignored"})))
-        }
+    if !queued {
+        info!(delivery_id, "Duplicate delivery, already queued");
     }
+
+    Ok(Json(serde_json::json!({"status": "queued"})))
 }
 
 async fn handle_issue(
     state: &AppState,
+    repo_cfg: &RepoConfig,
     payload: &WebhookPayload,
 ) -> Result<Json<serde_json::Value>, StatusCode> {
     let issue = payload.issue.as_ref().ok_or(StatusCode::BAD_REQUEST)?;
-
-    match payload.action.as_str() {
-        "opened" => {
-            info!(number = issue.number, title = %issue.title, "Adding issue to project");
-            let item_id = add_to_project(state, &issue.html_url).await?;
-            update_status(state, &item_id, "Todo").await?;
-            Ok(Json(serde_json::json!({"status": "added", "item_id": item_id})))
-        }
-        "closed" => {
-            info!(number = issue.number, title = %issue.title, "Moving issue to Done");
-            let item_id = add_to_project(state, &issue.html_url).await?;
-            update_status(state, &item_id, "Done").await?;
-            Ok(Json(serde_json::json!({"status": "done", "item_id": item_id})))
-        }
-        _ => Ok(Json(serde_json::json!({"status": "ignored"}))),
-    }
+    let label = payload.label.as_ref().map(|l| l.name.as_str());
+
+    let Some(status) = repo_cfg.resolve_status(&payload.action, label) else {
+        return Ok(Json(serde_json::json!({"status": "ignored"})));
+    };
+
+    let assignees = issue
+        .assignees
+        .iter()
+        .map(|a| a.login.as_str())
+        .collect::<Vec<_>>()
+        .join(",");
+    info!(
+        number = issue.number,
+        title = %issue.title,
+        labels = issue.labels.len(),
+        assignees,
+        status,
+        "Syncing issue"
+    );
+    let item_id = add_to_project(state, repo_cfg, &issue.html_url, Some(issue.number)).await?;
+    update_status(state, repo_cfg, &item_id, status, Some(issue.number)).await?;
+    Ok(Json(serde_json::json!({"status": "synced", "item_id": item_id})))
 }
 
 async fn handle_pr(
     state: &AppState,
+    repo_cfg: &RepoConfig,
     payload: &WebhookPayload,
 ) -> Result<Json<serde_json::Value>, StatusCode> {
     let pr = payload.pull_request.as_ref().ok_or(StatusCode::BAD_REQUEST)?;
+    let label = payload.label.as_ref().map(|l| l.name.as_str());
+
+    // A closed-but-unmerged PR is reported under a synthetic action so it
+    // can be mapped independently from (or left unmatched, like) a merge.
+    let action = if payload.action == "closed" && pr.merged != Some(true) {
+        "closed_unmerged"
+    } else {
+        payload.action.as_str()
+    };
+
+    let Some(status) = repo_cfg.resolve_status(action, label) else {
+        return Ok(Json(serde_json::json!({"status": "ignored"})));
+    };
+
+    let assignees = pr
+        .assignees
+        .iter()
+        .map(|a| a.login.as_str())
+        .collect::<Vec<_>>()
+        .join(",");
+    info!(
+        number = pr.number,
+        title = %pr.title,
+        draft = pr.draft,
+        labels = pr.labels.len(),
+        assignees,
+        status,
+        "Syncing pull request"
+    );
+    let item_id = add_to_project(state, repo_cfg, &pr.html_url, Some(pr.number)).await?;
+    update_status(state, repo_cfg, &item_id, status, Some(pr.number)).await?;
+    Ok(Json(serde_json::json!({"status": "synced", "item_id": item_id})))
+}
 
-    match payload.action.as_str() {
-        "opened" => {
-            info!(number = pr.number, title = %pr.title, "Adding PR to project");
-            let item_id = add_to_project(state, &pr.html_url).await?;
-            update_status(state, &item_id, "Todo").await?;
-            Ok(Json(serde_json::json!({"status": "added", "item_id": item_id})))
-        }
-        "closed" if pr.merged == Some(true) => {
-            info!(number = pr.number, title = %pr.title, "Moving merged PR to Done");
-            let item_id = add_to_project(state, &pr.html_url).await?;
-            update_status(state, &item_id, "Done").await?;
-            Ok(Json(serde_json::json!({"status": "done", "item_id": item_id})))
-        }
-        _ => Ok(Json(serde_json::json!({"status": "ignored"}))),
+/// Adds an item to the project board, notifying on failure.
+pub(crate) async fn add_to_project(
+    state: &AppState,
+    repo_cfg: &RepoConfig,
+    content_url: &str,
+    item_number: Option<u64>,
+) -> Result<String, StatusCode> {
+    state
+        .github
+        .add_to_project(&repo_cfg.project_id, content_url)
+        .await
+        .map_err(|e| {
+            error!("add_to_project failed: {e}");
+            notify_failure(state, &repo_cfg.full_name, "add_to_project", item_number, e.to_string());
+            StatusCode::BAD_GATEWAY
+        })
+        .map(|item_id| {
+            info!(item_id, "Item added to project");
+            item_id
+        })
+}
+
+/// Sets the Status field on a project item, notifying on failure.
+pub(crate) async fn update_status(
+    state: &AppState,
+    repo_cfg: &RepoConfig,
+    item_id: &str,
+    status: &str,
+    item_number: Option<u64>,
+) -> Result<(), StatusCode> {
+    let field_name = repo_cfg.status_field();
+    let option_name = repo_cfg.status_option(status);
+
+    state
+        .github
+        .update_status(&repo_cfg.project_id, item_id, field_name, option_name)
+        .await
+        .map_err(|e| {
+            error!("update_status failed: {e}");
+            notify_failure(state, &repo_cfg.full_name, "update_status", item_number, e.to_string());
+            StatusCode::BAD_GATEWAY
+        })?;
+
+    info!(item_id, status, "Status updated");
+    Ok(())
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
     }
+    a.iter()
+        .zip(b.iter())
+        .fold(0u8, |acc, (x, y)| acc | (x ^ y))
+        == 0
 }
 
-/// Add an item to the project via GraphQL, returns the item ID
-async fn add_to_project(state: &AppState, content_url: &str) -> Result<String, StatusCode> {
-    // First get the node ID of the issue/PR from the URL
-    let query = r#"mutation($projectId: ID!, $contentId: ID!) {
-        addProjectV2ItemById(input: {projectId: $projectId, contentId: $contentId}) {
-            item { id }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use github::RemoteItem;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    /// Records every node-ID, add-to-project and status-update call made
+    /// against it and returns canned responses, so webhook and handler logic
+    /// can be tested without the network. Status
+    /// updates are tracked per item so tests can assert the final state.
+    #[derive(Default)]
+    struct MockGitHubClient {
+        calls: Mutex<Vec<String>>,
+        statuses: Mutex<HashMap<String, String>>,
+    }
+
+    #[async_trait::async_trait]
+    impl GitHubClient for MockGitHubClient {
+        async fn get_node_id(&self, html_url: &str) -> anyhow::Result<String> {
+            self.calls.lock().unwrap().push(format!("get_node_id:{html_url}"));
+            Ok(format!("node_{html_url}"))
         }
-    }"#;
 
-    // We need the node_id. Get it from the REST API first.
-    let node_id = get_node_id(state, content_url).await?;
+        async fn add_to_project(&self, _project_id: &str, content_url: &str) -> anyhow::Result<String> {
+            self.calls.lock().unwrap().push(format!("add_to_project:{content_url}"));
+            Ok(format!("item_{content_url}"))
+        }
 
-    let body = serde_json::json!({
-        "query": query,
-        "variables": {
-            "projectId": state.project_id,
-            "contentId": node_id
+        async fn update_status(
+            &self,
+            _project_id: &str,
+            item_id: &str,
+            _field_name: &str,
+            option_name: &str,
+        ) -> anyhow::Result<()> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("update_status:{item_id}:{option_name}"));
+            self.statuses
+                .lock()
+                .unwrap()
+                .insert(item_id.to_string(), option_name.to_string());
+            Ok(())
         }
-    });
 
-    let resp = state
-        .http
-        .post("https://api.github.com/graphql")
-        .header("Authorization", format!("Bearer {}", state.github_token))
-        .header("User-Agent", "github-project-sync")
-        .json(&body)
-        .send()
-        .await
-        .map_err(|e| {
-            error!("GraphQL request failed: {e}");
-            StatusCode::BAD_GATEWAY
-        })?;
+        async fn list_issues(&self, _repo_full_name: &str) -> anyhow::Result<Vec<RemoteItem>> {
+            Ok(Vec::new())
+        }
 
-    let json: serde_json::Value = resp.json().await.map_err(|e| {
-        error!("Failed to parse GraphQL response: {e}");
-        StatusCode::BAD_GATEWAY
-    })?;
+        async fn project_item_statuses(
+            &self,
+            _project_id: &str,
+            _field_name: &str,
+        ) -> anyhow::Result<HashMap<String, String>> {
+            Ok(HashMap::new())
+        }
+    }
 
-    if let Some(errors) = json.get("errors") {
-        error!("GraphQL errors: {errors}");
+    fn test_config() -> Config {
+        Config {
+            repos: vec![RepoConfig {
+                full_name: "acme/widgets".to_string(),
+                project_id: "PVT_test".to_string(),
+                webhook_secrets: vec!["s3cr3t".to_string()],
+                status_field: None,
+                status_options: HashMap::new(),
+                rules: vec![
+                    crate::config::Rule {
+                        on: "opened".to_string(),
+                        label: None,
+                        set_status: "Todo".to_string(),
+                    },
+                    crate::config::Rule {
+                        on: "reopened".to_string(),
+                        label: None,
+                        set_status: "Todo".to_string(),
+                    },
+                    crate::config::Rule {
+                        on: "closed".to_string(),
+                        label: None,
+                        set_status: "Done".to_string(),
+                    },
+                    crate::config::Rule {
+                        on: "labeled".to_string(),
+                        label: Some("needs-review".to_string()),
+                        set_status: "In Review".to_string(),
+                    },
+                ],
+            }],
+            notifier: Default::default(),
+        }
     }
 
-    let item_id = json["data"]["addProjectV2ItemById"]["item"]["id"]
-        .as_str()
-        .ok_or_else(|| {
-            error!("No item ID in response: {json}");
-            StatusCode::BAD_GATEWAY
-        })?
-        .to_string();
+    async fn test_state(github: Arc<MockGitHubClient>) -> Arc<AppState> {
+        let queue = Queue::connect("sqlite::memory:").await.unwrap();
+        Arc::new(AppState {
+            config: Arc::new(test_config()),
+            http: Client::new(),
+            github,
+            queue,
+        })
+    }
 
-    info!(item_id, "Item added to project");
-    Ok(item_id)
-}
+    fn sign(secret: &str, body: &[u8]) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(body);
+        format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+    }
 
-/// Get the node_id from a GitHub URL like https://github.com/owner/repo/issues/1
-async fn get_node_id(state: &AppState, html_url: &str) -> Result<String, StatusCode> {
-    // Convert html_url to API URL
-    let api_url = html_url
-        .replace("https://github.com/", "https://api.github.com/repos/")
-        .replace("/pull/", "/pulls/");
-
-    let resp = state
-        .http
-        .get(&api_url)
-        .header("Authorization", format!("Bearer {}", state.github_token))
-        .header("User-Agent", "github-project-sync")
-        .header("Accept", "application/vnd.github+json")
-        .send()
-        .await
-        .map_err(|e| {
-            error!("REST API request failed: {e}");
-            StatusCode::BAD_GATEWAY
-        })?;
+    fn issue_payload(action: &str, full_name: &str) -> Vec<u8> {
+        serde_json::json!({
+            "action": action,
+            "issue": {
+                "html_url": "https://github.com/acme/widgets/issues/1",
+                "number": 1,
+                "title": "Bug"
+            },
+            "repository": { "full_name": full_name }
+        })
+        .to_string()
+        .into_bytes()
+    }
 
-    let json: serde_json::Value = resp.json().await.map_err(|e| {
-        error!("Failed to parse REST response: {e}");
-        StatusCode::BAD_GATEWAY
-    })?;
+    fn labeled_issue_payload(label: &str) -> Vec<u8> {
+        serde_json::json!({
+            "action": "labeled",
+            "label": { "name": label },
+            "issue": {
+                "html_url": "https://github.com/acme/widgets/issues/1",
+                "number": 1,
+                "title": "Bug"
+            },
+            "repository": { "full_name": "acme/widgets" }
+        })
+        .to_string()
+        .into_bytes()
+    }
 
-    json["node_id"]
-        .as_str()
-        .map(|s| s.to_string())
-        .ok_or_else(|| {
-            error!("No node_id in response: {json}");
-            StatusCode::BAD_GATEWAY
+    fn pr_payload(action: &str, merged: Option<bool>) -> Vec<u8> {
+        serde_json::json!({
+            "action": action,
+            "pull_request": {
+                "html_url": "https://github.com/acme/widgets/pull/2",
+                "number": 2,
+                "title": "Add feature",
+                "merged": merged
+            },
+            "repository": { "full_name": "acme/widgets" }
         })
-}
+        .to_string()
+        .into_bytes()
+    }
 
-/// Update the Status field on a project item
-async fn update_status(state: &AppState, item_id: &str, status: &str) -> Result<(), StatusCode> {
-    // First, get the Status field ID and option IDs
-    let field_query = r#"query($projectId: ID!) {
-        node(id: $projectId) {
-            ... on ProjectV2 {
-                fields(first: 20) {
-                    nodes {
-                        ... on ProjectV2SingleSelectField {
-                            id
-                            name
-                            options { id name }
-                        }
-                    }
-                }
-            }
+    fn webhook_headers(event: &str, sig: Option<&str>, delivery_id: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-github-event", event.parse().unwrap());
+        headers.insert("x-github-delivery", delivery_id.parse().unwrap());
+        if let Some(sig) = sig {
+            headers.insert("x-hub-signature-256", sig.parse().unwrap());
         }
-    }"#;
+        headers
+    }
 
-    let body = serde_json::json!({
-        "query": field_query,
-        "variables": { "projectId": state.project_id }
-    });
+    #[tokio::test]
+    async fn rejects_unsigned_webhook() {
+        let state = test_state(Arc::new(MockGitHubClient::default())).await;
+        let body = issue_payload("opened", "acme/widgets");
+        let headers = webhook_headers("issues", None, "d1");
 
-    let resp = state
-        .http
-        .post("https://api.github.com/graphql")
-        .header("Authorization", format!("Bearer {}", state.github_token))
-        .header("User-Agent", "github-project-sync")
-        .json(&body)
-        .send()
-        .await
-        .map_err(|e| {
-            error!("Failed to fetch project fields: {e}");
-            StatusCode::BAD_GATEWAY
-        })?;
+        let result = webhook(State(state), headers, Bytes::from(body)).await;
+        assert_eq!(result.err(), Some(StatusCode::UNAUTHORIZED));
+    }
 
-    let json: serde_json::Value = resp.json().await.map_err(|e| {
-        error!("Failed to parse fields response: {e}");
-        StatusCode::BAD_GATEWAY
-    })?;
+    #[tokio::test]
+    async fn rejects_wrong_signature() {
+        let state = test_state(Arc::new(MockGitHubClient::default())).await;
+        let body = issue_payload("opened", "acme/widgets");
+        let headers = webhook_headers("issues", Some("sha256=deadbeef"), "d2");
 
-    let fields = json["data"]["node"]["fields"]["nodes"]
-        .as_array()
-        .ok_or_else(|| {
-            error!("No fields found: {json}");
-            StatusCode::BAD_GATEWAY
-        })?;
+        let result = webhook(State(state), headers, Bytes::from(body)).await;
+        assert_eq!(result.err(), Some(StatusCode::UNAUTHORIZED));
+    }
 
-    // Find the Status field
-    let mut field_id = None;
-    let mut option_id = None;
-
-    for field in fields {
-        if field["name"].as_str() == Some("Status") {
-            field_id = field["id"].as_str().map(|s| s.to_string());
-            if let Some(options) = field["options"].as_array() {
-                for opt in options {
-                    if opt["name"].as_str() == Some(status) {
-                        option_id = opt["id"].as_str().map(|s| s.to_string());
-                        break;
-                    }
-                }
-            }
-            break;
+    #[tokio::test]
+    async fn ignores_untracked_repo_without_checking_signature() {
+        let state = test_state(Arc::new(MockGitHubClient::default())).await;
+        let body = issue_payload("opened", "someone/else");
+        let headers = webhook_headers("issues", None, "d3");
+
+        let result = webhook(State(state), headers, Bytes::from(body)).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn queues_signed_webhook_for_tracked_repo() {
+        let state = test_state(Arc::new(MockGitHubClient::default())).await;
+        let body = issue_payload("opened", "acme/widgets");
+        let sig = sign("s3cr3t", &body);
+        let headers = webhook_headers("issues", Some(&sig), "d4");
+
+        let result = webhook(State(state.clone()), headers, Bytes::from(body))
+            .await
+            .unwrap();
+        let _ = result;
+
+        let events = state.queue.claim_due(10).await.unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event_type, "issues");
+    }
+
+    #[tokio::test]
+    async fn duplicate_delivery_is_not_queued_twice() {
+        let state = test_state(Arc::new(MockGitHubClient::default())).await;
+        let body = issue_payload("opened", "acme/widgets");
+        let sig = sign("s3cr3t", &body);
+
+        for _ in 0..2 {
+            webhook(
+                State(state.clone()),
+                webhook_headers("issues", Some(&sig), "same-delivery"),
+                Bytes::from(body.clone()),
+            )
+            .await
+            .unwrap();
         }
+
+        let events = state.queue.claim_due(10).await.unwrap();
+        assert_eq!(events.len(), 1);
     }
 
-    let field_id = field_id.ok_or_else(|| {
-        error!("Status field not found");
-        StatusCode::BAD_GATEWAY
-    })?;
+    #[tokio::test]
+    async fn issue_opened_moves_to_todo() {
+        let mock = Arc::new(MockGitHubClient::default());
+        let state = test_state(mock.clone()).await;
+        let repo_cfg = &state.config.repos[0];
+        let payload: WebhookPayload =
+            serde_json::from_slice(&issue_payload("opened", "acme/widgets")).unwrap();
+
+        handle_issue(&state, repo_cfg, &payload).await.unwrap();
+
+        let statuses = mock.statuses.lock().unwrap();
+        assert_eq!(
+            statuses.get("item_https://github.com/acme/widgets/issues/1"),
+            Some(&"Todo".to_string())
+        );
+    }
 
-    let option_id = option_id.ok_or_else(|| {
-        error!("Status option '{status}' not found");
-        StatusCode::BAD_GATEWAY
-    })?;
+    #[tokio::test]
+    async fn issue_closed_moves_to_done() {
+        let mock = Arc::new(MockGitHubClient::default());
+        let state = test_state(mock.clone()).await;
+        let repo_cfg = &state.config.repos[0];
+        let payload: WebhookPayload =
+            serde_json::from_slice(&issue_payload("closed", "acme/widgets")).unwrap();
+
+        handle_issue(&state, repo_cfg, &payload).await.unwrap();
+
+        let statuses = mock.statuses.lock().unwrap();
+        assert_eq!(
+            statuses.get("item_https://github.com/acme/widgets/issues/1"),
+            Some(&"Done".to_string())
+        );
+    }
 
-    // Update the field
-    let mutation = r#"mutation($projectId: ID!, $itemId: ID!, $fieldId: ID!, $optionId: String!) {
-        updateProjectV2ItemFieldValue(input: {
-            projectId: $projectId
-            itemId: $itemId
-            fieldId: $fieldId
-            value: { singleSelectOptionId: $optionId }
-        }) {
-            projectV2Item { id }
-        }
-    }"#;
-
-    let body = serde_json::json!({
-        "query": mutation,
-        "variables": {
-            "projectId": state.project_id,
-            "itemId": item_id,
-            "fieldId": field_id,
-            "optionId": option_id
-        }
-    });
+    #[tokio::test]
+    async fn merged_pr_moves_to_done() {
+        let mock = Arc::new(MockGitHubClient::default());
+        let state = test_state(mock.clone()).await;
+        let repo_cfg = &state.config.repos[0];
+        let payload: WebhookPayload =
+            serde_json::from_slice(&pr_payload("closed", Some(true))).unwrap();
+
+        handle_pr(&state, repo_cfg, &payload).await.unwrap();
+
+        let statuses = mock.statuses.lock().unwrap();
+        assert_eq!(
+            statuses.get("item_https://github.com/acme/widgets/pull/2"),
+            Some(&"Done".to_string())
+        );
+    }
 
-    let resp = state
-        .http
-        .post("https://api.github.com/graphql")
-        .header("Authorization", format!("Bearer {}", state.github_token))
-        .header("User-Agent", "github-project-sync")
-        .json(&body)
-        .send()
-        .await
-        .map_err(|e| {
-            error!("Failed to update status: {e}");
-            StatusCode::BAD_GATEWAY
-        })?;
+    #[tokio::test]
+    async fn closed_unmerged_pr_is_ignored() {
+        let mock = Arc::new(MockGitHubClient::default());
+        let state = test_state(mock.clone()).await;
+        let repo_cfg = &state.config.repos[0];
+        let payload: WebhookPayload =
+            serde_json::from_slice(&pr_payload("closed", Some(false))).unwrap();
 
-    let json: serde_json::Value = resp.json().await.map_err(|e| {
-        error!("Failed to parse update response: {e}");
-        StatusCode::BAD_GATEWAY
-    })?;
+        handle_pr(&state, repo_cfg, &payload).await.unwrap();
 
-    if let Some(errors) = json.get("errors") {
-        error!("Status update errors: {errors}");
-        return Err(StatusCode::BAD_GATEWAY);
+        assert!(mock.calls.lock().unwrap().is_empty());
     }
 
-    info!(item_id, status, "Status updated");
-    Ok(())
-}
+    #[tokio::test]
+    async fn matching_label_rule_moves_to_configured_column() {
+        let mock = Arc::new(MockGitHubClient::default());
+        let state = test_state(mock.clone()).await;
+        let repo_cfg = &state.config.repos[0];
+        let payload: WebhookPayload =
+            serde_json::from_slice(&labeled_issue_payload("needs-review")).unwrap();
+
+        handle_issue(&state, repo_cfg, &payload).await.unwrap();
+
+        let statuses = mock.statuses.lock().unwrap();
+        assert_eq!(
+            statuses.get("item_https://github.com/acme/widgets/issues/1"),
+            Some(&"In Review".to_string())
+        );
+    }
 
-fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
-    if a.len() != b.len() {
-        return false;
+    #[tokio::test]
+    async fn unconfigured_label_is_ignored() {
+        let mock = Arc::new(MockGitHubClient::default());
+        let state = test_state(mock.clone()).await;
+        let repo_cfg = &state.config.repos[0];
+        let payload: WebhookPayload =
+            serde_json::from_slice(&labeled_issue_payload("wontfix")).unwrap();
+
+        handle_issue(&state, repo_cfg, &payload).await.unwrap();
+
+        assert!(mock.calls.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn reopened_issue_moves_back_to_todo() {
+        let mock = Arc::new(MockGitHubClient::default());
+        let state = test_state(mock.clone()).await;
+        let repo_cfg = &state.config.repos[0];
+        let payload: WebhookPayload =
+            serde_json::from_slice(&issue_payload("reopened", "acme/widgets")).unwrap();
+
+        handle_issue(&state, repo_cfg, &payload).await.unwrap();
+
+        let statuses = mock.statuses.lock().unwrap();
+        assert_eq!(
+            statuses.get("item_https://github.com/acme/widgets/issues/1"),
+            Some(&"Todo".to_string())
+        );
     }
-    a.iter()
-        .zip(b.iter())
-        .fold(0u8, |acc, (x, y)| acc | (x ^ y))
-        == 0
 }
@@ -0,0 +1,81 @@
+use reqwest::Client;
+use serde::Serialize;
+use tracing::error;
+
+use crate::config::{NotifierConfig, SmtpConfig};
+
+/// Details about a sync failure, sent to whatever sinks are configured.
+#[derive(Debug, Serialize)]
+pub(crate) struct Failure {
+    pub(crate) repo: String,
+    pub(crate) event: String,
+    pub(crate) item_number: Option<u64>,
+    pub(crate) detail: String,
+}
+
+/// Sends a failure notification to every configured sink. A no-op unless
+/// `notifier.enabled` is set in the config file.
+pub(crate) async fn notify(http: &Client, config: &NotifierConfig, failure: Failure) {
+    if !config.enabled {
+        return;
+    }
+
+    if let Some(url) = &config.webhook_url {
+        if let Err(e) = send_webhook(http, url, &failure).await {
+            error!("Failed to send failure webhook: {e}");
+        }
+    }
+
+    if let Some(smtp) = &config.smtp {
+        if let Err(e) = send_email(smtp, &failure).await {
+            error!("Failed to send failure email: {e}");
+        }
+    }
+}
+
+async fn send_webhook(http: &Client, url: &str, failure: &Failure) -> anyhow::Result<()> {
+    http.post(url)
+        .json(failure)
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
+
+async fn send_email(smtp: &SmtpConfig, failure: &Failure) -> anyhow::Result<()> {
+    use lettre::message::Message;
+    use lettre::transport::smtp::authentication::Credentials;
+    use lettre::{SmtpTransport, Transport};
+
+    let item = failure
+        .item_number
+        .map(|n| format!("#{n}"))
+        .unwrap_or_else(|| "-".to_string());
+
+    let body = format!(
+        "repo: {}\nevent: {}\nitem: {}\ndetail: {}",
+        failure.repo, failure.event, item, failure.detail
+    );
+
+    let email = Message::builder()
+        .from(smtp.from.parse()?)
+        .to(smtp.to.parse()?)
+        .subject(format!("github-project-sync: sync failure for {}", failure.repo))
+        .body(body)?;
+
+    // lettre's SmtpTransport is blocking; run the connect+send on a blocking
+    // thread so a slow or unreachable relay doesn't stall a Tokio worker.
+    let smtp = smtp.clone();
+    tokio::task::spawn_blocking(move || -> anyhow::Result<()> {
+        let mailer = SmtpTransport::relay(&smtp.host)?
+            .port(smtp.port)
+            .credentials(Credentials::new(smtp.username.clone(), smtp.password.clone()))
+            .build();
+
+        mailer.send(&email)?;
+        Ok(())
+    })
+    .await??;
+
+    Ok(())
+}
@@ -0,0 +1,163 @@
+use std::str::FromStr;
+
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions};
+use sqlx::Row;
+
+/// Max attempts before an event is parked as permanently `Failed`.
+const MAX_ATTEMPTS: i64 = 5;
+/// Exponential backoff delays (seconds), capped at the last entry.
+const BACKOFF_SECONDS: &[i64] = &[1, 4, 16];
+
+/// A webhook event durably queued for processing, keyed by GitHub's
+/// `x-github-delivery` header so redelivered webhooks don't double-process.
+#[derive(Debug)]
+pub struct QueuedEvent {
+    pub id: i64,
+    pub event_type: String,
+    pub repo_full_name: String,
+    pub payload: Vec<u8>,
+    pub attempts: i64,
+}
+
+/// SQLite-backed queue of webhook deliveries awaiting (or retrying)
+/// processing. Inserting is synchronous with the webhook response; draining
+/// happens on a background worker so a slow or down GitHub API never holds
+/// up the webhook handler.
+#[derive(Clone)]
+pub struct Queue {
+    pool: SqlitePool,
+}
+
+impl Queue {
+    pub async fn connect(database_url: &str) -> anyhow::Result<Queue> {
+        let options = SqliteConnectOptions::from_str(database_url)?.create_if_missing(true);
+        let pool = SqlitePoolOptions::new().connect_with(options).await?;
+        sqlx::query(
+            r#"CREATE TABLE IF NOT EXISTS events (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                delivery_id TEXT NOT NULL UNIQUE,
+                event_type TEXT NOT NULL,
+                repo_full_name TEXT NOT NULL,
+                payload BLOB NOT NULL,
+                state TEXT NOT NULL DEFAULT 'pending',
+                attempts INTEGER NOT NULL DEFAULT 0,
+                next_attempt_at INTEGER NOT NULL DEFAULT 0,
+                created_at INTEGER NOT NULL
+            )"#,
+        )
+        .execute(&pool)
+        .await?;
+
+        // Any row still `in_progress` belongs to a worker that died or was
+        // restarted mid-claim; requeue it so it isn't orphaned forever.
+        sqlx::query("UPDATE events SET state = 'pending' WHERE state = 'in_progress'")
+            .execute(&pool)
+            .await?;
+
+        Ok(Queue { pool })
+    }
+
+    /// Inserts a new pending event. Returns `false` without inserting if
+    /// `delivery_id` has already been queued, so a redelivered webhook is a
+    /// no-op.
+    pub async fn enqueue(
+        &self,
+        delivery_id: &str,
+        event_type: &str,
+        repo_full_name: &str,
+        payload: &[u8],
+    ) -> anyhow::Result<bool> {
+        let now = now_unix();
+        let result = sqlx::query(
+            "INSERT OR IGNORE INTO events \
+             (delivery_id, event_type, repo_full_name, payload, next_attempt_at, created_at) \
+             VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(delivery_id)
+        .bind(event_type)
+        .bind(repo_full_name)
+        .bind(payload)
+        .bind(now)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Atomically flips up to `limit` pending events whose backoff has
+    /// elapsed to `in_progress` and returns them, oldest first, so that
+    /// running more than one worker never drains the same row twice.
+    pub async fn claim_due(&self, limit: i64) -> anyhow::Result<Vec<QueuedEvent>> {
+        let now = now_unix();
+        let rows = sqlx::query(
+            "UPDATE events SET state = 'in_progress' \
+             WHERE id IN ( \
+                 SELECT id FROM events \
+                 WHERE state = 'pending' AND next_attempt_at <= ? \
+                 ORDER BY id LIMIT ? \
+             ) \
+             RETURNING id, event_type, repo_full_name, payload, attempts",
+        )
+        .bind(now)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut events: Vec<QueuedEvent> = rows
+            .into_iter()
+            .map(|row| QueuedEvent {
+                id: row.get("id"),
+                event_type: row.get("event_type"),
+                repo_full_name: row.get("repo_full_name"),
+                payload: row.get("payload"),
+                attempts: row.get("attempts"),
+            })
+            .collect();
+        // RETURNING yields rows in no defined order.
+        events.sort_by_key(|event| event.id);
+        Ok(events)
+    }
+
+    pub async fn mark_done(&self, id: i64) -> anyhow::Result<()> {
+        sqlx::query("UPDATE events SET state = 'done' WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Records a failed attempt: schedules an exponential-backoff retry, or
+    /// parks the event as `Failed` once `MAX_ATTEMPTS` is reached.
+    pub async fn mark_failed(&self, id: i64, attempts: i64) -> anyhow::Result<()> {
+        if attempts >= MAX_ATTEMPTS {
+            sqlx::query("UPDATE events SET state = 'failed', attempts = ? WHERE id = ?")
+                .bind(attempts)
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+            return Ok(());
+        }
+
+        let delay = BACKOFF_SECONDS
+            .get((attempts - 1).max(0) as usize)
+            .copied()
+            .unwrap_or(*BACKOFF_SECONDS.last().unwrap());
+
+        sqlx::query(
+            "UPDATE events SET state = 'pending', attempts = ?, next_attempt_at = ? WHERE id = ?",
+        )
+        .bind(attempts)
+        .bind(now_unix() + delay)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+}
+
+fn now_unix() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64
+}
@@ -0,0 +1,87 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use tracing::{error, info};
+
+use crate::config::RepoConfig;
+use crate::{add_to_project, update_status, AppState};
+
+/// How often each tracked repo is reconciled against its project board.
+const DEFAULT_RECONCILE_INTERVAL: Duration = Duration::from_secs(600);
+
+/// Runs forever, periodically repairing project boards that drifted out of
+/// sync because a webhook was dropped (GitHub downtime, delivery failure,
+/// or a gap while this service was restarting).
+pub(crate) async fn run(state: Arc<AppState>) -> anyhow::Result<()> {
+    let interval = std::env::var("RECONCILE_INTERVAL_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_RECONCILE_INTERVAL);
+
+    loop {
+        tokio::time::sleep(interval).await;
+        reconcile_all(&state).await;
+    }
+}
+
+async fn reconcile_all(state: &AppState) {
+    for repo_cfg in &state.config.repos {
+        if let Err(e) = reconcile_repo(state, repo_cfg).await {
+            error!(repo = repo_cfg.full_name, "Reconciliation failed: {e}");
+        }
+    }
+}
+
+async fn reconcile_repo(state: &AppState, repo_cfg: &RepoConfig) -> anyhow::Result<()> {
+    let items = state.github.list_issues(&repo_cfg.full_name).await?;
+    let board_statuses = state
+        .github
+        .project_item_statuses(&repo_cfg.project_id, repo_cfg.status_field())
+        .await?;
+
+    let todo_option = repo_cfg.status_option("Todo");
+    let done_option = repo_cfg.status_option("Done");
+
+    let mut repaired = 0;
+    for item in items {
+        // Skip states the default webhook rules don't sync either (e.g. a
+        // closed but unmerged PR). Reconcile only ever sets "Todo" or
+        // "Done"; a repo's configured rules are not consulted.
+        let Some(expected_status) = item.expected_status() else {
+            continue;
+        };
+
+        let current = board_statuses.get(&item.html_url).map(String::as_str);
+
+        // Only repair items missing from the board or with no status value
+        // set, or ones whose
+        // open/closed state is flatly wrong (still "Todo" after closing, or
+        // still "Done" while open). An open item sitting in some other
+        // configured column (e.g. a label rule's "In Review") was put there
+        // on purpose and isn't reconcile's to override.
+        let needs_repair = match current {
+            None => true,
+            Some(value) if expected_status == "Done" => value == todo_option,
+            Some(value) => value == done_option,
+        };
+
+        if !needs_repair {
+            continue;
+        }
+
+        let item_id = add_to_project(state, repo_cfg, &item.html_url, Some(item.number))
+            .await
+            .map_err(|s| anyhow::anyhow!("add_to_project failed: {s:?}"))?;
+        update_status(state, repo_cfg, &item_id, expected_status, Some(item.number))
+            .await
+            .map_err(|s| anyhow::anyhow!("update_status failed: {s:?}"))?;
+        repaired += 1;
+    }
+
+    if repaired > 0 {
+        info!(repo = repo_cfg.full_name, repaired, "Reconciled drifted items");
+    }
+
+    Ok(())
+}